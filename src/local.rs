@@ -1,11 +1,188 @@
-use crate::{Domain, Global, HazardPointer};
+use crate::{Domain, HazardPointer};
 use crate::pointer::{Reclaim, Pointer};
 use core::cell::{RefCell, Cell};
+use core::ops::{Deref, DerefMut};
+use std::any::Any;
+use std::collections::HashMap;
 use std::mem;
+use std::sync::{Mutex, OnceLock};
 use std::thread_local;
 
 thread_local! {
-    static DEF_LOCAL_RETIRED: RefCell<LocalBag<'static, Global>> = RefCell::new(LocalBag::new(Domain::global()));
+    // Free-list of hazard pointers released by `LocalBag::reclaim`, reused by
+    // `HazardPointer::acquire_pooled` instead of allocating a fresh slot.
+    static HP_POOL: RefCell<Vec<HazardPointer<'static>>> = RefCell::new(Vec::new());
+    // One `LocalBag` per domain this thread has retired into, keyed by the
+    // domain's address. Type-erased because each domain can carry a
+    // different `F` family; `Domain::with_local_bag` downcasts it back.
+    static LOCAL_BAGS: RefCell<HashMap<usize, Box<dyn Any>>> = RefCell::new(HashMap::new());
+}
+
+// Entries a terminating thread's `LocalBag` could not immediately reclaim
+// (still guarded), handed off via `Domain::adopt_retired` and keyed by the
+// same domain address as `LOCAL_BAGS`. Unlike the thread-local maps above,
+// this is process-wide: any thread's later `LocalBag::reclaim` for that
+// domain drains and retries them.
+static ADOPTED_RETIRED: OnceLock<Mutex<HashMap<usize, Vec<LocalRetired>>>> = OnceLock::new();
+
+fn adopted_retired() -> &'static Mutex<HashMap<usize, Vec<LocalRetired>>> {
+    ADOPTED_RETIRED.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+// Safety: `LocalRetired` is only ever constructed by `LocalBag::retire`/
+// `retire_pp` from a `T: Send`, so moving the erased (ptr, deleter) pair to
+// whichever thread eventually reclaims it is sound.
+unsafe impl Send for LocalRetired {}
+
+impl<F: 'static> Domain<F> {
+    /// Retires `ptr` into this domain's thread-local bag, allocating the bag
+    /// on first use by this thread.
+    ///
+    /// Requires `&'static self`, since the bag is keyed by this domain's
+    /// address for the lifetime of the thread (see `with_local_bag`). A
+    /// domain that isn't `'static` — stack-allocated, or held behind an
+    /// `Arc` that can be dropped — can't use this fast path at all; retire
+    /// through the domain's non-local API instead.
+    #[allow(missing_docs)]
+    pub fn retire_local<T>(&'static self, ptr: *mut T)
+    where
+        T: Send
+    {
+        self.with_local_bag(|bag| unsafe { bag.retire::<_, Box<_>>(ptr) })
+    }
+
+    /// HP++ counterpart of [`Domain::retire_local`]. Same `&'static self`
+    /// requirement and the same caveat for non-`'static` domains.
+    #[allow(missing_docs)]
+    pub fn retire_local_pp<T>(&'static self, ptr: *mut T)
+    where
+        T: Send
+    {
+        self.with_local_bag(|bag| unsafe { bag.retire_pp::<_, Box<_>>(ptr) })
+    }
+
+    /// Sets the slack factor `k` used to size this thread's scan threshold
+    /// (`H * (1 + k)`, see [`LocalBag::set_reclamation_factor`]) for this
+    /// domain's thread-local bag, allocating the bag on first use by this
+    /// thread if needed.
+    #[allow(missing_docs)]
+    pub fn set_reclamation_factor(&'static self, k: f64) {
+        self.with_local_bag(|bag| bag.set_reclamation_factor(k))
+    }
+
+    // Keyed by `self`'s address rather than a separately allocated id. This
+    // is only collision-free because the receiver is `&'static self`: a
+    // domain that could be dropped and have a new one allocated at the same
+    // address would silently adopt its predecessor's bag (the `.expect()`
+    // below only catches a differing `F`, not this case). Do not relax the
+    // `'static` bound without giving domains a real stable identity.
+    #[allow(clippy::mutable_key_type)]
+    pub(crate) fn with_local_bag<R>(&'static self, f: impl FnOnce(&mut LocalBag<'static, F>) -> R) -> R {
+        let key = self as *const Self as usize;
+        LOCAL_BAGS.with(|bags| {
+            let mut bags = bags.borrow_mut();
+            // `bags.borrow_mut()` is already held for the whole call, so the
+            // bag itself doesn't need its own `RefCell` on top.
+            let bag = bags
+                .entry(key)
+                .or_insert_with(|| Box::new(LocalBag::new(self)) as Box<dyn Any>)
+                .downcast_mut::<LocalBag<'static, F>>()
+                .expect("domain address reused by a different domain family");
+            f(bag)
+        })
+    }
+}
+
+// Split from the `F: 'static` impl above: these don't type-erase into
+// `Box<dyn Any>`, so they don't need that bound, and `LocalBag::Drop` (which
+// cannot add bounds beyond the struct's own, unlike an inherent impl) relies
+// on calling them without it.
+impl<F> Domain<F> {
+    /// Accepts a retired entry a terminating thread's `LocalBag` could not
+    /// free (something still guards it), folding it into this domain's
+    /// shared pending list instead of leaving the thread to spin on it. Any
+    /// thread's next `LocalBag::reclaim` for this domain drains the list and
+    /// retries freeing each entry against a fresh guarded-pointer scan.
+    ///
+    /// This entry is only ever drained by that future `reclaim` (called from
+    /// `retire`/`retire_pp`), or by the reclamation pass in a `LocalBag`'s own
+    /// `Drop`, for the same domain address. If no thread ever retires into or drops a `LocalBag`
+    /// for this domain again, the entry sits in `ADOPTED_RETIRED` forever:
+    /// this is a permanent leak, not just one deferred to process exit.
+    pub(crate) unsafe fn adopt_retired(
+        &'static self,
+        ptr: *mut dyn Reclaim,
+        deleter: unsafe fn(ptr: *mut dyn Reclaim),
+    ) {
+        let key = self as *const Self as usize;
+        adopted_retired()
+            .lock()
+            .unwrap()
+            .entry(key)
+            .or_default()
+            .push(LocalRetired { ptr, deleter });
+    }
+
+    fn take_adopted_retired(&'static self) -> Vec<LocalRetired> {
+        let key = self as *const Self as usize;
+        adopted_retired().lock().unwrap().remove(&key).unwrap_or_default()
+    }
+}
+
+/// A [`HazardPointer`] drawn from [`HazardPointer::acquire_pooled`]. Instead of
+/// being dropped when it goes out of scope, it returns its slot to the
+/// thread-local pool so the next `try_unlink`/`protect_raw` cycle can reuse it.
+pub struct PooledHazardPointer<'domain> {
+    inner: Option<HazardPointer<'domain>>,
+}
+
+impl<'domain> Deref for PooledHazardPointer<'domain> {
+    type Target = HazardPointer<'domain>;
+
+    fn deref(&self) -> &Self::Target {
+        self.inner.as_ref().unwrap()
+    }
+}
+
+impl<'domain> DerefMut for PooledHazardPointer<'domain> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.inner.as_mut().unwrap()
+    }
+}
+
+impl<'domain> Drop for PooledHazardPointer<'domain> {
+    fn drop(&mut self) {
+        if let Some(mut hp) = self.inner.take() {
+            // Clear the slot's protection before parking it: otherwise it
+            // keeps advertising the last pointer it protected to
+            // `collect_guarded_ptrs()` for as long as it sits idle in the
+            // pool, stalling reclamation of that object.
+            hp.reset_protection();
+            // Safety: slots only ever move between `HP_POOL` and a `LocalBag`
+            // of the same thread, so the erased `'static` lifetime never
+            // outlives the `'domain` it was actually protecting.
+            let hp: HazardPointer<'static> = unsafe { mem::transmute(hp) };
+            // `try_with`, not `with`: this `drop` can run while a `LocalBag`
+            // is being torn down during thread exit, and `HP_POOL`'s own
+            // destructor may have already run (TLS destructors on the same
+            // thread don't guarantee an order between independently
+            // registered locals). If the pool is already gone there's
+            // nothing to park the slot in, so just let `hp` drop instead of
+            // panicking the unwind (or aborting it, if we're already
+            // unwinding from a panic).
+            let _ = HP_POOL.try_with(|pool| pool.borrow_mut().push(hp));
+        }
+    }
+}
+
+impl HazardPointer<'static> {
+    #[allow(missing_docs)]
+    pub fn acquire_pooled() -> PooledHazardPointer<'static> {
+        let inner = HP_POOL
+            .with(|pool| pool.borrow_mut().pop())
+            .unwrap_or_else(HazardPointer::new);
+        PooledHazardPointer { inner: Some(inner) }
+    }
 }
 
 #[inline]
@@ -14,9 +191,7 @@ pub fn retire_locally<T>(ptr: *mut T)
 where
     T: Send
 {
-    DEF_LOCAL_RETIRED.with(|r| {
-        unsafe { r.borrow_mut().retire::<_, Box<_>>(ptr) }
-    })
+    Domain::global().retire_local(ptr)
 }
 
 #[inline]
@@ -25,9 +200,7 @@ pub fn retire_locally_pp<T>(ptr: *mut T)
 where
     T: Send
 {
-    DEF_LOCAL_RETIRED.with(|r| {
-        unsafe { r.borrow_mut().retire_pp::<_, Box<_>>(ptr) }
-    })
+    Domain::global().retire_local_pp(ptr)
 }
 
 #[allow(missing_docs)]
@@ -45,7 +218,7 @@ where
     let mut hps: Vec<_> = links
         .iter()
         .map(|&ptr| {
-            let mut hp = HazardPointer::new();
+            let mut hp = HazardPointer::acquire_pooled();
             hp.protect_raw(ptr);
             hp
         })
@@ -56,8 +229,7 @@ where
         for &ptr in to_be_unlinked {
             set_stop(ptr);
         }
-        DEF_LOCAL_RETIRED.with(|r| {
-            let mut local = r.borrow_mut();
+        Domain::global().with_local_bag(|local| {
             local.hps.append(&mut hps);
             for &ptr in to_be_unlinked {
                 unsafe { local.retire_pp::<_, Box<_>>(ptr) }
@@ -92,23 +264,57 @@ pub struct LocalBag<'s, F> {
     domain: &'s Domain<F>,
     // It contains pairs of (pointer, deleter)
     retired: Vec<LocalRetired>,
-    // Used for HP++
-    hps: Vec<HazardPointer<'s>>,
-    collect_count: Cell<usize>,
+    // Used for HP++. Pooled so that `reclaim` returns these slots to the
+    // thread-local free list instead of dropping them outright.
+    hps: Vec<PooledHazardPointer<'s>>,
+    // `retired.len()` at which the next scan should fire. See `reclaim` for
+    // how the threshold is derived from `H`, the hazard pointers seen live.
+    scan_threshold: Cell<usize>,
+    // Slack factor `k` in `H * (1 + k)`; see `set_reclamation_factor`.
+    k: Cell<f64>,
 }
 
-impl<'s, F> LocalBag<'s, F> {
-    const COUNTS_BETWEEN_COLLECT: usize = 128;
+// `'s: 'static` mirrors the bound on `Domain::with_local_bag`, the only
+// place a `LocalBag` is actually constructed: the thread-local/handoff
+// registries above all key on a domain's address, which is only sound for
+// domains that live for `'static` (see `with_local_bag`).
+impl<'s, F> LocalBag<'s, F>
+where
+    's: 'static,
+{
+    // Default slack, in the 0.25-1.0 range suggested by the original Hazard
+    // Pointers paper's amortized-O(1) bound.
+    const DEFAULT_K: f64 = 0.5;
+    // Floor under `H * (1 + k)`: also the bootstrap threshold before the
+    // first scan has sampled `H`. Without this floor, a domain with few or
+    // no live hazard pointers collapses the threshold to near zero and a
+    // scan fires on every retirement instead of being amortized.
+    const MIN_SCAN_THRESHOLD: usize = 128;
 
     pub fn new(domain: &'s Domain<F>) -> Self {
         Self {
             domain,
             retired: Vec::new(),
             hps: Vec::new(),
-            collect_count: Cell::new(0)
+            scan_threshold: Cell::new(Self::MIN_SCAN_THRESHOLD),
+            k: Cell::new(Self::DEFAULT_K),
         }
     }
 
+    /// Sets the slack factor `k` used to size the scan threshold as
+    /// `H * (1 + k)`, where `H` is the number of hazard pointers observed
+    /// live at the scan that computes it. The threshold already in place is
+    /// not recomputed: the new `k` first applies to the threshold computed at
+    /// the end of the next scan. Larger `k` trades more transient garbage
+    /// for fewer, cheaper scans.
+    pub fn set_reclamation_factor(&self, k: f64) {
+        self.k.set(k);
+    }
+
+    fn should_scan(&self) -> bool {
+        self.retired.len() >= self.scan_threshold.get()
+    }
+
     pub unsafe fn retire<T, P>(&mut self, ptr: *mut T)
     where
         T: Send,
@@ -121,33 +327,10 @@ impl<'s, F> LocalBag<'s, F> {
                 let _ = P::from_raw(ptr as *mut T);
             })
         });
-        let collect_count = self.collect_count.get().wrapping_add(1);
-        self.collect_count.set(collect_count);
-
-        if collect_count % Self::COUNTS_BETWEEN_COLLECT == 0 {
-            self.do_reclamation();
+        if self.should_scan() {
+            self.reclaim(false);
         }
     }
-    
-    #[inline]
-    fn do_reclamation(&mut self) {
-        membarrier::heavy();
-        let guarded_ptrs = self.domain.collect_guarded_ptrs();
-        self.retired = self.retired
-            .iter()
-            .filter_map(|element| {
-                if guarded_ptrs.contains(&(element.ptr as *mut u8)) {
-                    Some(LocalRetired {
-                        ptr: element.ptr,
-                        deleter: element.deleter
-                    })
-                } else {
-                    unsafe { (element.deleter)(element.ptr) };
-                    None
-                }
-            })
-            .collect();
-    }
 
     pub unsafe fn retire_pp<T, P>(&mut self, ptr: *mut T)
     where
@@ -161,42 +344,82 @@ impl<'s, F> LocalBag<'s, F> {
                 let _ = P::from_raw(ptr as *mut T);
             })
         });
-        let collect_count = self.collect_count.get().wrapping_add(1);
-        self.collect_count.set(collect_count);
-
-        if collect_count % Self::COUNTS_BETWEEN_COLLECT == 0 {
-            self.do_reclamation_pp();
+        if self.should_scan() {
+            self.reclaim(true);
         }
     }
 
+    /// Reclamation pass shared by `retire` and `retire_pp`: one
+    /// `membarrier::heavy()` and one `collect_guarded_ptrs()` scan, used to
+    /// sieve the retire list. When `drop_hps` is set, the HP++ hazard
+    /// pointers gathered since the last pass are released first, before the
+    /// barrier and scan.
+    ///
+    /// The barrier and scan must run *after* every entry they sieve —
+    /// including anything just folded in from `take_adopted_retired` above
+    /// — is retired/adopted, or a reader that set its hazard pointer after
+    /// an earlier scan would be invisible to it, and this would free a node
+    /// it's still guarding. So this cannot reuse a scan taken for a
+    /// previous call, however recent; each call pays for its own.
     #[inline]
-    fn do_reclamation_pp(&mut self) {
-        membarrier::heavy();
-        drop(mem::replace(&mut self.hps, Vec::new()));
+    fn reclaim(&mut self, drop_hps: bool) {
+        if drop_hps {
+            drop(mem::replace(&mut self.hps, Vec::new()));
+        }
 
+        self.retired.extend(self.domain.take_adopted_retired());
+
+        membarrier::heavy();
         let guarded_ptrs = self.domain.collect_guarded_ptrs();
-        self.retired = self.retired
-            .iter()
-            .filter_map(|element| {
-                if guarded_ptrs.contains(&(element.ptr as *mut u8)) {
-                    Some(LocalRetired {
-                        ptr: element.ptr,
-                        deleter: element.deleter
-                    })
-                } else {
-                    unsafe { (element.deleter)(element.ptr) };
-                    None
-                }
-            })
-            .collect();
+        let h = guarded_ptrs.len();
+        self.retired.retain(|element| {
+            if guarded_ptrs.contains(&(element.ptr as *mut u8)) {
+                true
+            } else {
+                unsafe { (element.deleter)(element.ptr) };
+                false
+            }
+        });
+        let threshold = ((h as f64) * (1.0 + self.k.get())).ceil() as usize;
+        self.scan_threshold.set(threshold.max(Self::MIN_SCAN_THRESHOLD));
     }
 }
 
 impl<'s, F> Drop for LocalBag<'s, F> {
     fn drop(&mut self) {
-        while !self.retired.is_empty() {
-            self.do_reclamation();
-            core::hint::spin_loop();
+        // Drop can't carry the `'s: 'static` bound `reclaim`/`adopt_retired`
+        // need (E0367 forbids a Drop impl stricter than its struct), but
+        // every `LocalBag` in this crate is in fact built from a `'static`
+        // domain via `Domain::with_local_bag`.
+        // Safety: see above; `domain` really does live for `'static` here.
+        let domain: &'static Domain<F> = unsafe { mem::transmute(self.domain) };
+
+        // One best-effort pass: anything another thread is still guarding
+        // survives it and is handed off to the domain below, rather than
+        // spinning here indefinitely waiting for that thread to let go.
+        // `self.hps` needs no special handling: it's dropped along with the
+        // rest of `self` once this function returns, which tries to return
+        // each slot to `HP_POOL` via `PooledHazardPointer::drop`. If this is
+        // itself running as part of thread teardown and `HP_POOL` has
+        // already been torn down, that return is a silent no-op rather than
+        // a panic, so the slots are simply lost with the thread.
+        self.retired.extend(domain.take_adopted_retired());
+        membarrier::heavy();
+        let guarded_ptrs = domain.collect_guarded_ptrs();
+        self.retired.retain(|element| {
+            if guarded_ptrs.contains(&(element.ptr as *mut u8)) {
+                true
+            } else {
+                unsafe { (element.deleter)(element.ptr) };
+                false
+            }
+        });
+
+        for element in self.retired.drain(..) {
+            // Safety: `element` was only ever produced by `retire`/`retire_pp`
+            // above, so its (ptr, deleter) pair upholds the same invariants
+            // the domain already relies on for its own retired list.
+            unsafe { domain.adopt_retired(element.ptr, element.deleter) };
         }
     }
 }
\ No newline at end of file